@@ -49,7 +49,7 @@ fn main() {
         // we should leave this up to the implementation tbh
         // let mut writer_buf = buffered_io::asynch::BufferedWrite::new(writer);
 
-        tiny_cli::run(
+        tiny_cli::run::<_, _, _, 16>(
             &mut StdinReader(stdin),
             &mut StdoutWriter(stdout),
             MyHandler,
@@ -59,12 +59,15 @@ fn main() {
     });
 }
 
-async fn handle_cli<'a, 'b, W: embedded_io_async::Write>(
-    ctx: &'a mut Context<'b, W>,
+async fn handle_cli<'b, W: embedded_io_async::Write>(
+    ctx: &mut Context<'b, W>,
     level: Level<'b>,
 ) -> Result<(), W::Error> {
     // nested levels and exec / exec_arg
-    if let Some(level) = ctx.command(level, "config").await? {
+    if let Some(level) = ctx
+        .command_with_help(level, "config", "view or change configuration")
+        .await?
+    {
         for name in ["enable", "logging", "logfile", "connetion", "constant"] {
             if let Some(level) = ctx.command(level, name).await? {
                 // config items get/set
@@ -92,15 +95,21 @@ async fn handle_cli<'a, 'b, W: embedded_io_async::Write>(
         }
     }
 
-    if let Some(level) = ctx.command(level, "save").await? {
+    if let Some(level) = ctx
+        .command_with_help(level, "save", "save the current configuration")
+        .await?
+    {
         if ctx.exec(level) {
-            ctx.print(format!("save")).await?;
+            ctx.print("save".to_string()).await?;
         }
     }
 
-    if let Some(level) = ctx.command(level, "reboot").await? {
+    if let Some(level) = ctx
+        .command_with_help(level, "reboot", "restart the device")
+        .await?
+    {
         if ctx.exec(level) {
-            ctx.print(format!("reboot")).await?;
+            ctx.print("reboot".to_string()).await?;
         }
     }
 
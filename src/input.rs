@@ -0,0 +1,169 @@
+//! Byte-at-a-time parser turning raw terminal input into [`Action`]s.
+//!
+//! Handles plain UTF-8 text, the C0 control characters the CLI cares about,
+//! and `ESC [ <final>` CSI escape sequences (arrow keys and friends).
+
+use crate::utf8::{Utf8Char, Utf8Decoder};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ControlCharacter {
+    CtrlA,
+    CtrlC,
+    CtrlD,
+    CtrlE,
+    CtrlH,
+    CtrlR,
+    CtrlU,
+    CtrlW,
+    Tab,
+    CarriageReturn,
+    Backspace,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Action {
+    /// A complete, printable UTF-8 character.
+    Print(Utf8Char),
+    /// A recognized control character.
+    ControlCharacter(ControlCharacter),
+    /// The cursor-up arrow key (`ESC [ A`).
+    CursorUp,
+    /// The cursor-down arrow key (`ESC [ B`).
+    CursorDown,
+    /// The cursor-right arrow key (`ESC [ C`).
+    CursorRight,
+    /// The cursor-left arrow key (`ESC [ D`).
+    CursorLeft,
+    /// A lone `ESC` keypress, not the start of a recognized CSI sequence.
+    Escape,
+    /// Terminal's reply to a Device Status Report query (`ESC [ 6 n`),
+    /// decoded from `ESC [ <row> ; <col> R`.
+    CursorPosition { col: usize },
+    /// Byte consumed but no action produced yet, e.g. mid-escape-sequence
+    /// or an escape sequence we don't recognize.
+    None,
+}
+
+/// Longest CSI parameter string (e.g. `"24;80"`) we bother collecting;
+/// anything past this is ignored, not buffered.
+const CSI_PARAMS_LEN: usize = 16;
+
+enum State {
+    Normal,
+    Escape,
+    Csi {
+        params: [u8; CSI_PARAMS_LEN],
+        len: usize,
+    },
+}
+
+pub struct Parser {
+    state: State,
+    utf8: Utf8Decoder,
+    /// A byte that arrived right after a lone `ESC` and turned out not to be
+    /// the start of a CSI sequence. It's the caller's next real keystroke,
+    /// so it's stashed here instead of being dropped; [`Parser::take_pending`]
+    /// hands it back so the caller can run it through `advance` again.
+    pending: Option<u8>,
+}
+
+impl Parser {
+    pub const fn new() -> Self {
+        Self {
+            state: State::Normal,
+            utf8: Utf8Decoder::new(),
+            pending: None,
+        }
+    }
+
+    pub fn advance(&mut self, byte: u8) -> Action {
+        match &mut self.state {
+            State::Normal => match byte {
+                0x1B => {
+                    self.state = State::Escape;
+                    Action::None
+                }
+                _ => match control_character(byte) {
+                    Some(cc) => Action::ControlCharacter(cc),
+                    // other C0 control bytes aren't printable, drop them
+                    None if byte < 0x20 => Action::None,
+                    None => match self.utf8.advance(byte) {
+                        Some(utf8_char) => Action::Print(utf8_char),
+                        None => Action::None,
+                    },
+                },
+            },
+            State::Escape => {
+                if byte == b'[' {
+                    self.state = State::Csi {
+                        params: [0; CSI_PARAMS_LEN],
+                        len: 0,
+                    };
+                    Action::None
+                } else {
+                    self.state = State::Normal;
+                    self.pending = Some(byte);
+                    Action::Escape
+                }
+            }
+            State::Csi { params, len } => match byte {
+                // final byte of the CSI sequence
+                0x40..=0x7E => {
+                    let params = &params[..*len];
+                    let action = decode_csi(params, byte);
+                    self.state = State::Normal;
+                    action
+                }
+                // parameter/intermediate byte, keep collecting
+                _ => {
+                    if *len < params.len() {
+                        params[*len] = byte;
+                        *len += 1;
+                    }
+                    Action::None
+                }
+            },
+        }
+    }
+
+    /// Takes the byte stashed by a just-resolved lone `ESC`, if any, so the
+    /// caller can feed it through `advance` again instead of losing it.
+    pub fn take_pending(&mut self) -> Option<u8> {
+        self.pending.take()
+    }
+}
+
+fn control_character(byte: u8) -> Option<ControlCharacter> {
+    match byte {
+        0x01 => Some(ControlCharacter::CtrlA),
+        0x03 => Some(ControlCharacter::CtrlC),
+        0x04 => Some(ControlCharacter::CtrlD),
+        0x05 => Some(ControlCharacter::CtrlE),
+        0x08 => Some(ControlCharacter::CtrlH),
+        0x09 => Some(ControlCharacter::Tab),
+        0x0D => Some(ControlCharacter::CarriageReturn),
+        0x12 => Some(ControlCharacter::CtrlR),
+        0x15 => Some(ControlCharacter::CtrlU),
+        0x17 => Some(ControlCharacter::CtrlW),
+        0x7F => Some(ControlCharacter::Backspace),
+        _ => None,
+    }
+}
+
+fn decode_csi(params: &[u8], final_byte: u8) -> Action {
+    match final_byte {
+        b'A' => Action::CursorUp,
+        b'B' => Action::CursorDown,
+        b'C' => Action::CursorRight,
+        b'D' => Action::CursorLeft,
+        // Device Status Report reply: "<row>;<col>R"
+        b'R' => match core::str::from_utf8(params) {
+            Ok(params) => match params.split_once(';').and_then(|(_, col)| col.parse().ok()) {
+                Some(col) => Action::CursorPosition { col },
+                None => Action::None,
+            },
+            Err(_) => Action::None,
+        },
+        _ => Action::None,
+    }
+}
@@ -1,23 +1,34 @@
 // #![cfg_attr(not(test), no_std)]
 #![allow(async_fn_in_trait)]
 
-use heapless::String;
+use core::fmt::Write as _;
+use heapless::{Deque, String};
 
 mod input;
 mod utf8;
 
 use input::Parser;
 
+/// Filler used to pad completion candidates out to a column's width.
+const PAD: &[u8] = b"                                                                ";
+
+/// Column command names are padded to in a `?`/`help` listing, before the
+/// description.
+const HELP_NAME_COLUMN: usize = 16;
+
 /// Zero-allocation handler trait using a GAT for the returned future.
 /// The returned future’s lifetime `'a` is tied to the borrowed `Context`/`Level`.
 pub trait Handle<W: embedded_io_async::Write> {
     async fn handle<'a>(&self, ctx: &mut Context<'a, W>, level: Level<'a>) -> Result<(), W::Error>;
 }
 
+/// Runs the CLI, reading from `reader` and writing to `writer`, keeping the
+/// last `HISTORY_LEN` entered lines around for Up/Down recall.
 pub async fn run<
     R: embedded_io_async::Read,
     W: embedded_io_async::Write<Error = R::Error>,
     H: Handle<W>,
+    const HISTORY_LEN: usize,
 >(
     reader: &mut R,
     writer: &mut W,
@@ -28,109 +39,331 @@ pub async fn run<
 
     // currently entered line
     let mut line: String<64> = String::new();
+    // byte index of the cursor within `line`
+    let mut cursor: usize = 0;
+
+    // previously entered lines, most recent at the back
+    let mut history: Deque<String<64>, HISTORY_LEN> = Deque::new();
+    // how many entries back from the newest we're currently showing, if any
+    let mut history_cursor: Option<usize> = None;
+    // `line` as it was before Up was first pressed, restored once the user
+    // walks back past the newest history entry
+    let mut saved_line: String<64> = String::new();
+
+    // reverse-incremental history search (Ctrl-R), if currently active
+    let mut search: Option<SearchState> = None;
+
+    // Detect the terminal width for multi-column completion listings: save
+    // the cursor, jump far right, then ask for the cursor position. Falls
+    // back to 80 columns if the reply never comes. The reply is handled by
+    // the `A::CursorPosition` arm below like any other byte, since a real
+    // keystroke can arrive in the same read as (or before) the reply.
+    writer.write_all(b"\x1B[s\x1B[999C\x1B[6n").await?;
+    writer.flush().await?;
+    let mut width: usize = 80;
 
     loop {
         let n = reader.read(&mut buf).await?;
 
         for &byte in &buf[..n] {
-            let action = parser.advance(byte);
-
-            use input::{Action as A, ControlCharacter as CC};
-
-            match action {
-                // exit CLI
-                A::ControlCharacter(CC::CtrlC | CC::CtrlD) if line.is_empty() => {
-                    return Ok(());
-                }
-                // clear line
-                A::ControlCharacter(CC::CtrlC) => {
-                    // carriage return, then clear after cursor
-                    writer.write_all(b"\r\x1B[0K> ").await?;
-                    line.clear();
-                }
-                // write
-                A::Print(utf8_char) => {
-                    let utf8_char = unsafe { core::str::from_utf8_unchecked(utf8_char.as_bytes()) };
-                    if let Ok(_) = line.push_str(utf8_char) {
-                        writer.write_all(utf8_char.as_bytes()).await?;
+            // a lone ESC is only recognized once the byte after it proves it
+            // isn't the start of a CSI sequence; that byte is the user's next
+            // real keystroke, so loop to run it through `advance` again
+            // rather than letting `parser` swallow it
+            let mut byte = byte;
+            loop {
+                let action = parser.advance(byte);
+
+                use input::{Action as A, ControlCharacter as CC};
+
+                if let Some(state) = &mut search {
+                    match action {
+                        // abort, restoring the line as it was before Ctrl-R
+                        A::ControlCharacter(CC::CtrlC) | A::Escape => {
+                            line = state.saved_line.clone();
+                            cursor = line.len();
+                            search = None;
+                            writer.write_all(b"\r\x1B[0K> ").await?;
+                            writer.write_all(line.as_bytes()).await?;
+                        }
+                        // accept the current match into `line` and leave search mode
+                        A::ControlCharacter(CC::CarriageReturn) => {
+                            if let Some((_, matched)) =
+                                find_match(&history, &state.query, state.depth.unwrap_or(0))
+                            {
+                                line.clear();
+                                let _ = line.push_str(matched);
+                            }
+                            cursor = line.len();
+                            search = None;
+                            writer.write_all(b"\r\x1B[0K> ").await?;
+                            writer.write_all(line.as_bytes()).await?;
+                        }
+                        // advance to the next older match
+                        A::ControlCharacter(CC::CtrlR) => {
+                            let skip = state.depth.map_or(0, |depth| depth + 1);
+                            if let Some((depth, _)) = find_match(&history, &state.query, skip) {
+                                state.depth = Some(depth);
+                            }
+                            redraw_search(writer, &history, state).await?;
+                        }
+                        // shorten the query and search again from the newest entry
+                        A::ControlCharacter(CC::Backspace | CC::CtrlH) => {
+                            state.query.pop();
+                            state.depth =
+                                find_match(&history, &state.query, 0).map(|(depth, _)| depth);
+                            redraw_search(writer, &history, state).await?;
+                        }
+                        // extend the query and search again from the newest entry
+                        A::Print(utf8_char) => {
+                            let utf8_char =
+                                unsafe { core::str::from_utf8_unchecked(utf8_char.as_bytes()) };
+                            if state.query.push_str(utf8_char).is_ok() {
+                                state.depth =
+                                    find_match(&history, &state.query, 0).map(|(depth, _)| depth);
+                                redraw_search(writer, &history, state).await?;
+                            }
+                        }
+                        _ => (),
                     }
-                }
-                // backspace
-                A::ControlCharacter(CC::Backspace | CC::CtrlH) => {
-                    if let Some(_) = line.pop() {
-                        // backspace + delete after cursor
-                        writer.write_all(b"\x08\x1B[0K").await?;
+                    match parser.take_pending() {
+                        Some(pending) => byte = pending,
+                        None => break,
                     }
+                    continue;
                 }
-                // enter
-                A::ControlCharacter(CC::CarriageReturn) => {
-                    let level = Level::new(&line);
-                    let mut ctx_type = ContextType::Execute;
-                    let mut ctx = Context::new(&mut ctx_type, writer);
-                    handle.handle(&mut ctx, level).await?;
-                    line.clear();
-                    writer.write_all(b"\r\n> ").await?;
-                }
-                // autocomplete
-                A::ControlCharacter(CC::Tab) => {
-                    // split off last part of command ("_" is whitespace)
-                    //
-                    // "cmd1" no whitespace at end, try to complete cmd1
-                    // rsplit_once returns None -> to_complete = line
-                    //
-                    // "cmd1_" whitespace at end, try to complete next command
-                    // rsplit_once returns Some("cmd1", "") -> cmd_path = "cmd1", to_complete = ""
-
-                    let (command_path, to_complete) = match line.rsplit_once(char::is_whitespace) {
-                        // no whitespace ->
-                        None => ("", line.as_str()),
-                        Some(x) => x,
-                    };
-
-                    // first try to complete the last word in-line, eg "con" -> "config"
-                    // if that fails, eg. because there is also "connection", print all available options
-                    let level = Level::new(command_path);
-                    let mut autocomplete_best_match = None;
-                    let mut exact_match = false;
-                    let mut ctx_type = ContextType::AutocompleteBestMatch {
-                        autocomplete_best_match: &mut autocomplete_best_match,
-                        exact_match: &mut exact_match,
-                        to_complete,
-                    };
-
-                    let mut ctx = Context::new(&mut ctx_type, writer);
-                    handle.handle(&mut ctx, level).await?;
-
-                    match autocomplete_best_match {
-                        // no completion found
-                        None => (),
-                        // multiple matches diverge at the first character
-                        // this should always happen after pressing tab twice
-                        // list all matching completions
-                        Some("") => {
-                            let mut ctx_type = ContextType::AutocompleteList(to_complete);
+
+                match action {
+                    // exit CLI
+                    A::ControlCharacter(CC::CtrlC | CC::CtrlD) if line.is_empty() => {
+                        return Ok(());
+                    }
+                    // clear line
+                    A::ControlCharacter(CC::CtrlC) => {
+                        // carriage return, then clear after cursor
+                        writer.write_all(b"\r\x1B[0K> ").await?;
+                        line.clear();
+                        cursor = 0;
+                    }
+                    // enter reverse-incremental history search
+                    A::ControlCharacter(CC::CtrlR) => {
+                        let state = search.insert(SearchState {
+                            query: String::new(),
+                            saved_line: line.clone(),
+                            depth: None,
+                        });
+                        redraw_search(writer, &history, state).await?;
+                    }
+                    // write
+                    A::Print(utf8_char) => {
+                        let utf8_char =
+                            unsafe { core::str::from_utf8_unchecked(utf8_char.as_bytes()) };
+                        if insert_at(&mut line, cursor, utf8_char) {
+                            cursor += utf8_char.len();
+                            history_cursor = None;
+                            writer.write_all(utf8_char.as_bytes()).await?;
+                            let tail = &line[cursor..];
+                            if !tail.is_empty() {
+                                writer.write_all(tail.as_bytes()).await?;
+                                move_cursor(writer, tail.chars().count(), b'D').await?;
+                            }
+                        }
+                    }
+                    // backspace: delete the character before the cursor
+                    A::ControlCharacter(CC::Backspace | CC::CtrlH) if cursor > 0 => {
+                        let ch = line[..cursor].chars().next_back().unwrap();
+                        let start = cursor - ch.len_utf8();
+                        delete_range(&mut line, start, cursor);
+                        cursor = start;
+                        history_cursor = None;
+                        redraw_tail(writer, 1, &line[cursor..]).await?;
+                    }
+                    // move the cursor one character left/right within the line
+                    A::CursorLeft if cursor > 0 => {
+                        let ch = line[..cursor].chars().next_back().unwrap();
+                        cursor -= ch.len_utf8();
+                        move_cursor(writer, 1, b'D').await?;
+                    }
+                    A::CursorRight if cursor < line.len() => {
+                        let ch = line[cursor..].chars().next().unwrap();
+                        cursor += ch.len_utf8();
+                        move_cursor(writer, 1, b'C').await?;
+                    }
+                    // jump to the start/end of the line
+                    A::ControlCharacter(CC::CtrlA) => {
+                        let cols = line[..cursor].chars().count();
+                        cursor = 0;
+                        move_cursor(writer, cols, b'D').await?;
+                    }
+                    A::ControlCharacter(CC::CtrlE) => {
+                        let cols = line[cursor..].chars().count();
+                        cursor = line.len();
+                        move_cursor(writer, cols, b'C').await?;
+                    }
+                    // delete the word before the cursor
+                    A::ControlCharacter(CC::CtrlW) => {
+                        let start = prev_word_boundary(&line[..cursor]);
+                        if start < cursor {
+                            let moved = line[start..cursor].chars().count();
+                            delete_range(&mut line, start, cursor);
+                            cursor = start;
+                            history_cursor = None;
+                            redraw_tail(writer, moved, &line[cursor..]).await?;
+                        }
+                    }
+                    // kill from the start of the line up to the cursor
+                    A::ControlCharacter(CC::CtrlU) if cursor > 0 => {
+                        let moved = line[..cursor].chars().count();
+                        delete_range(&mut line, 0, cursor);
+                        cursor = 0;
+                        history_cursor = None;
+                        redraw_tail(writer, moved, &line).await?;
+                    }
+                    // recall the previous (or, on repeat, older) history entry
+                    A::CursorUp if !history.is_empty() => {
+                        if history_cursor.is_none() {
+                            saved_line = line.clone();
+                        }
+                        let depth = history_cursor
+                            .map_or(0, |depth| depth + 1)
+                            .min(history.len() - 1);
+                        history_cursor = Some(depth);
+                        line = history.iter().rev().nth(depth).unwrap().clone();
+                        cursor = line.len();
+                        writer.write_all(b"\r\x1B[0K> ").await?;
+                        writer.write_all(line.as_bytes()).await?;
+                    }
+                    // step back towards the newest history entry, then to the
+                    // in-progress edit that was stashed before the first CursorUp
+                    A::CursorDown => {
+                        if let Some(depth) = history_cursor {
+                            history_cursor = if depth == 0 { None } else { Some(depth - 1) };
+                            line = match history_cursor {
+                                Some(depth) => history.iter().rev().nth(depth).unwrap().clone(),
+                                None => saved_line.clone(),
+                            };
+                            cursor = line.len();
+                            writer.write_all(b"\r\x1B[0K> ").await?;
+                            writer.write_all(line.as_bytes()).await?;
+                        }
+                    }
+                    // enter
+                    A::ControlCharacter(CC::CarriageReturn) => {
+                        history_cursor = None;
+                        cursor = 0;
+
+                        if let Some((command_path, prefix)) = help_query(line.trim_end()) {
+                            let level = Level::new(command_path);
+                            let mut ctx_type = ContextType::Help { prefix };
                             let mut ctx = Context::new(&mut ctx_type, writer);
                             handle.handle(&mut ctx, level).await?;
-                            if ctx.printed_stuff {
-                                writer.write_all(b"\r\n> ").await?;
-                                writer.write_all(line.as_bytes()).await?;
+                        } else {
+                            if !line.is_empty() && history.back() != Some(&line) {
+                                if history.is_full() {
+                                    history.pop_front();
+                                }
+                                let _ = history.push_back(line.clone());
                             }
+
+                            let level = Level::new(&line);
+                            let mut ctx_type = ContextType::Execute;
+                            let mut ctx = Context::new(&mut ctx_type, writer);
+                            handle.handle(&mut ctx, level).await?;
                         }
-                        // non-empty match found -> can append completion to line
-                        Some(complete) => {
-                            if let Ok(_) = line.push_str(complete) {
+
+                        line.clear();
+                        writer.write_all(b"\r\n> ").await?;
+                    }
+                    // autocomplete
+                    A::ControlCharacter(CC::Tab) => {
+                        // split off last part of command ("_" is whitespace)
+                        //
+                        // "cmd1" no whitespace at end, try to complete cmd1
+                        // rsplit_once returns None -> to_complete = line
+                        //
+                        // "cmd1_" whitespace at end, try to complete next command
+                        // rsplit_once returns Some("cmd1", "") -> cmd_path = "cmd1", to_complete = ""
+
+                        let (command_path, to_complete) =
+                            match line.rsplit_once(char::is_whitespace) {
+                                // no whitespace ->
+                                None => ("", line.as_str()),
+                                Some(x) => x,
+                            };
+
+                        // first try to complete the last word in-line, eg "con" -> "config"
+                        // if that fails, eg. because there is also "connection", print all available options
+                        let level = Level::new(command_path);
+                        let mut autocomplete_best_match = None;
+                        let mut exact_match = false;
+                        let mut ctx_type = ContextType::AutocompleteBestMatch {
+                            autocomplete_best_match: &mut autocomplete_best_match,
+                            exact_match: &mut exact_match,
+                            to_complete,
+                        };
+
+                        let mut ctx = Context::new(&mut ctx_type, writer);
+                        handle.handle(&mut ctx, level).await?;
+
+                        match autocomplete_best_match {
+                            // no completion found
+                            None => (),
+                            // multiple matches diverge at the first character
+                            // this should always happen after pressing tab twice
+                            // list all matching completions
+                            Some("") => {
+                                // first pass: find the longest candidate so we know how
+                                // many columns fit in the terminal width
+                                let mut max_len = 0;
+                                let mut ctx_type = ContextType::AutocompleteMeasure {
+                                    to_complete,
+                                    max_len: &mut max_len,
+                                };
+                                let mut ctx = Context::new(&mut ctx_type, writer);
+                                handle.handle(&mut ctx, level).await?;
+
+                                let column_width = max_len + 2;
+                                let columns = (width / column_width).max(1);
+
+                                // second pass: print the candidates in a grid
+                                let mut ctx_type = ContextType::AutocompleteList {
+                                    to_complete,
+                                    columns,
+                                    column_width,
+                                };
+                                let mut ctx = Context::new(&mut ctx_type, writer);
+                                handle.handle(&mut ctx, level).await?;
+                                if ctx.printed_stuff {
+                                    writer.write_all(b"\r\n> ").await?;
+                                    writer.write_all(line.as_bytes()).await?;
+                                }
+                            }
+                            // non-empty match found -> can append completion to line
+                            Some(complete) if line.push_str(complete).is_ok() => {
                                 writer.write_all(complete.as_bytes()).await?;
+                                cursor = line.len();
 
-                                if exact_match {
-                                    if let Ok(_) = line.push_str(" ") {
-                                        writer.write_all(b" ").await?;
-                                    }
+                                if exact_match && line.push_str(" ").is_ok() {
+                                    writer.write_all(b" ").await?;
+                                    cursor = line.len();
                                 }
                             }
+                            // didn't fit, drop it
+                            Some(_) => (),
                         }
                     }
+                    // reply to the width-probing DSR query sent at startup
+                    A::CursorPosition { col } => {
+                        width = col;
+                        writer.write_all(b"\x1B[u").await?;
+                    }
+                    _ => (),
+                }
+
+                match parser.take_pending() {
+                    Some(pending) => byte = pending,
+                    None => break,
                 }
-                _ => (),
             }
         }
 
@@ -146,7 +379,23 @@ enum ContextType<'a> {
         exact_match: &'a mut bool,
         to_complete: &'a str,
     },
-    AutocompleteList(&'a str),
+    /// First pass over a completion listing: find the longest matching
+    /// candidate so `AutocompleteList` can lay out columns before printing.
+    AutocompleteMeasure {
+        to_complete: &'a str,
+        max_len: &'a mut usize,
+    },
+    AutocompleteList {
+        to_complete: &'a str,
+        columns: usize,
+        column_width: usize,
+    },
+    /// Listing subcommands (and their descriptions) at a level, requested by
+    /// typing `?` or `help`. `prefix` filters candidates the same way
+    /// `to_complete` does for autocompletion, empty if none was given.
+    Help {
+        prefix: &'a str,
+    },
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -165,6 +414,9 @@ pub struct Context<'a, W: embedded_io_async::Write> {
     printed_stuff: bool,
     done: bool,
     writer: &'a mut W,
+    // number of candidates printed so far in an `AutocompleteList` pass,
+    // used to decide when to wrap to the next row
+    list_count: usize,
 }
 
 impl<'a, W: embedded_io_async::Write> Context<'a, W> {
@@ -174,6 +426,7 @@ impl<'a, W: embedded_io_async::Write> Context<'a, W> {
             printed_stuff: false,
             done: false,
             writer,
+            list_count: 0,
         }
     }
 
@@ -207,12 +460,24 @@ impl<'a, W: embedded_io_async::Write> Context<'a, W> {
         &mut self,
         level: Level<'l>,
         name: &'static str,
+    ) -> Result<Option<Level<'l>>, W::Error> {
+        self.command_with_help(level, name, "").await
+    }
+
+    /// Like [`Context::command`], but attaches a one-line description that's
+    /// shown alongside `name` when the user requests help (`?` or `help`) at
+    /// this level.
+    pub async fn command_with_help<'l>(
+        &mut self,
+        level: Level<'l>,
+        name: &'static str,
+        desc: &'static str,
     ) -> Result<Option<Level<'l>>, W::Error> {
         if self.done {
             return Ok(None);
         }
 
-        // line empty -> reached target level, do autocomplete
+        // line empty -> reached target level, do autocomplete/help
         // otherwise try descending command structure
         if !level.line.is_empty() {
             // check if the command matches exactly
@@ -231,7 +496,7 @@ impl<'a, W: embedded_io_async::Write> Context<'a, W> {
                 }
             }
         } else {
-            self.hint_autocomplete(name).await?;
+            self.hint(name, desc).await?;
         }
 
         Ok(None)
@@ -255,7 +520,11 @@ impl<'a, W: embedded_io_async::Write> Context<'a, W> {
     }
 
     pub async fn hint_autocomplete(&mut self, name: &'static str) -> Result<(), W::Error> {
-        // check if the command can be autocompleted
+        self.hint(name, "").await
+    }
+
+    async fn hint(&mut self, name: &'static str, desc: &'static str) -> Result<(), W::Error> {
+        // check if the command can be autocompleted, listed, or described
         match self.ctx_type {
             ContextType::AutocompleteBestMatch {
                 autocomplete_best_match,
@@ -274,12 +543,48 @@ impl<'a, W: embedded_io_async::Write> Context<'a, W> {
                     }
                 }
             }
-            ContextType::AutocompleteList(to_complete) => {
+            ContextType::AutocompleteMeasure {
+                to_complete,
+                max_len,
+            } => {
                 if name.starts_with(*to_complete) {
-                    if self.printed_stuff {
-                        self.print(b" ").await?;
+                    **max_len = (**max_len).max(name.len());
+                }
+            }
+            ContextType::AutocompleteList {
+                to_complete,
+                columns,
+                column_width,
+            } => {
+                let (to_complete, columns, column_width) = (*to_complete, *columns, *column_width);
+                if name.starts_with(to_complete) {
+                    if self.list_count > 0 && self.list_count.is_multiple_of(columns) {
+                        self.print(b"\r\n").await?;
+                    }
+                    self.print(name).await?;
+                    if !(self.list_count + 1).is_multiple_of(columns) {
+                        let pad = column_width.saturating_sub(name.len()).min(PAD.len());
+                        self.print(&PAD[..pad]).await?;
+                    }
+                    self.list_count += 1;
+                }
+            }
+            ContextType::Help { prefix } => {
+                let prefix = *prefix;
+                if name.starts_with(prefix) {
+                    if self.list_count > 0 {
+                        self.print(b"\r\n").await?;
                     }
                     self.print(name).await?;
+                    if !desc.is_empty() {
+                        let pad = HELP_NAME_COLUMN
+                            .saturating_sub(name.len())
+                            .max(1)
+                            .min(PAD.len());
+                        self.print(&PAD[..pad]).await?;
+                        self.print(desc).await?;
+                    }
+                    self.list_count += 1;
                 }
             }
             ContextType::Execute => (),
@@ -301,3 +606,157 @@ fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
 
     &a[..end]
 }
+
+/// Insert `s` into `line` at byte offset `at` (a char boundary), shifting
+/// the tail right. Returns `false` without modifying `line` if it wouldn't
+/// fit.
+fn insert_at(line: &mut String<64>, at: usize, s: &str) -> bool {
+    if line.len() + s.len() > line.capacity() {
+        return false;
+    }
+    // SAFETY: `at` is a char boundary and `s` is valid UTF-8, so splicing its
+    // bytes in and rotating the tail past them keeps `line` valid UTF-8.
+    unsafe {
+        let vec = line.as_mut_vec();
+        let _ = vec.extend_from_slice(s.as_bytes());
+        vec[at..].rotate_right(s.len());
+    }
+    true
+}
+
+/// Remove the byte range `start..end` (char boundaries) from `line`.
+fn delete_range(line: &mut String<64>, start: usize, end: usize) {
+    // SAFETY: `start` and `end` are char boundaries, so removing the bytes
+    // between them keeps `line` valid UTF-8.
+    unsafe {
+        let vec = line.as_mut_vec();
+        vec.copy_within(end.., start);
+        let new_len = vec.len() - (end - start);
+        vec.truncate(new_len);
+    }
+}
+
+/// Byte offset within `s` (a prefix of the line up to the cursor) of the
+/// start of the word immediately before the cursor, skipping trailing
+/// whitespace first — used by Ctrl-W.
+fn prev_word_boundary(s: &str) -> usize {
+    let mut chars = s.char_indices().rev().peekable();
+    let mut idx = s.len();
+
+    while let Some(&(i, c)) = chars.peek() {
+        if !c.is_whitespace() {
+            break;
+        }
+        idx = i;
+        chars.next();
+    }
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_whitespace() {
+            break;
+        }
+        idx = i;
+        chars.next();
+    }
+
+    idx
+}
+
+/// If `line` (already right-trimmed) ends in a bare `?` or `help` token,
+/// returns the `(command_path, prefix)` split for the text before it: all
+/// complete words form `command_path`, and the final word (if any) becomes
+/// `prefix`, filtered the same way Tab-completion's `to_complete` is. Used
+/// to route Enter into a help listing instead of execution.
+fn help_query(line: &str) -> Option<(&str, &str)> {
+    for token in ["?", "help"] {
+        let before = if line == token {
+            ""
+        } else if let Some(rest) = line.strip_suffix(token) {
+            match rest.strip_suffix(char::is_whitespace) {
+                Some(before) => before,
+                None => continue,
+            }
+        } else {
+            continue;
+        };
+
+        return Some(match before.rsplit_once(char::is_whitespace) {
+            None => ("", before),
+            Some(split) => split,
+        });
+    }
+    None
+}
+
+/// Move the terminal cursor `n` columns in `direction` (`b'C'` right,
+/// `b'D'` left), emitting nothing if `n` is zero.
+async fn move_cursor<W: embedded_io_async::Write>(
+    writer: &mut W,
+    n: usize,
+    direction: u8,
+) -> Result<(), W::Error> {
+    if n == 0 {
+        return Ok(());
+    }
+    let mut seq: String<16> = String::new();
+    let _ = write!(seq, "\x1B[{}{}", n, direction as char);
+    writer.write_all(seq.as_bytes()).await
+}
+
+/// After deleting text ending at the cursor: move left `moved` columns to
+/// the deletion point, clear to the end of the line, reprint `tail`, then
+/// move back so the cursor rests at the deletion point again.
+async fn redraw_tail<W: embedded_io_async::Write>(
+    writer: &mut W,
+    moved: usize,
+    tail: &str,
+) -> Result<(), W::Error> {
+    move_cursor(writer, moved, b'D').await?;
+    writer.write_all(b"\x1B[0K").await?;
+    writer.write_all(tail.as_bytes()).await?;
+    move_cursor(writer, tail.chars().count(), b'D').await
+}
+
+/// State for an active Ctrl-R reverse-incremental history search.
+struct SearchState {
+    query: String<64>,
+    /// `line` as it was before Ctrl-R was pressed, restored on abort.
+    saved_line: String<64>,
+    /// Depth (from the newest entry) of the currently displayed match, if
+    /// `query` has one.
+    depth: Option<usize>,
+}
+
+/// First history entry, at least `skip` entries back from the newest, that
+/// contains `query`. Returns its depth (from the newest entry) and
+/// text. An empty `query` never matches.
+fn find_match<'h, const N: usize>(
+    history: &'h Deque<String<64>, N>,
+    query: &str,
+    skip: usize,
+) -> Option<(usize, &'h str)> {
+    if query.is_empty() {
+        return None;
+    }
+    history
+        .iter()
+        .rev()
+        .enumerate()
+        .skip(skip)
+        .find(|(_, entry)| entry.contains(query))
+        .map(|(depth, entry)| (depth, entry.as_str()))
+}
+
+/// Render the `(reverse-i-search)` prompt for the current query and match.
+async fn redraw_search<W: embedded_io_async::Write, const N: usize>(
+    writer: &mut W,
+    history: &Deque<String<64>, N>,
+    state: &SearchState,
+) -> Result<(), W::Error> {
+    writer.write_all(b"\r\x1B[0K(reverse-i-search)`").await?;
+    writer.write_all(state.query.as_bytes()).await?;
+    writer.write_all(b"': ").await?;
+    if let Some((_, matched)) = find_match(history, &state.query, state.depth.unwrap_or(0)) {
+        writer.write_all(matched.as_bytes()).await?;
+    }
+    Ok(())
+}
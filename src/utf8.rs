@@ -0,0 +1,70 @@
+//! Incremental, allocation-free UTF-8 decoding for single bytes trickling in
+//! from a terminal.
+
+/// A decoded UTF-8 code point, stored inline without allocation.
+#[derive(Debug, Clone, Copy)]
+pub struct Utf8Char {
+    buf: [u8; 4],
+    len: usize,
+}
+
+impl Utf8Char {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+/// Feed bytes in one at a time; yields a [`Utf8Char`] once a full code point
+/// has been assembled.
+pub struct Utf8Decoder {
+    buf: [u8; 4],
+    len: usize,
+    remaining: usize,
+}
+
+impl Utf8Decoder {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; 4],
+            len: 0,
+            remaining: 0,
+        }
+    }
+
+    pub fn advance(&mut self, byte: u8) -> Option<Utf8Char> {
+        if self.remaining == 0 {
+            self.len = 0;
+            self.remaining = sequence_len(byte) - 1;
+            self.buf[self.len] = byte;
+            self.len += 1;
+        } else {
+            self.buf[self.len] = byte;
+            self.len += 1;
+            self.remaining -= 1;
+        }
+
+        if self.remaining == 0 {
+            // drop sequences that don't decode, e.g. a stray continuation byte
+            core::str::from_utf8(&self.buf[..self.len]).ok()?;
+            Some(Utf8Char {
+                buf: self.buf,
+                len: self.len,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Number of bytes in the UTF-8 sequence starting with `first_byte`.
+/// Invalid leading bytes are treated as a single byte so the decoder can't
+/// get stuck waiting for continuation bytes that will never arrive.
+fn sequence_len(first_byte: u8) -> usize {
+    match first_byte {
+        0x00..=0x7F => 1,
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => 1,
+    }
+}